@@ -1,6 +1,12 @@
-use wasm_bindgen::prelude::*;
+use std::collections::HashMap;
+
 use wgpu::util::DeviceExt;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
 use web_sys::{HtmlCanvasElement, window};
+#[cfg(target_arch = "wasm32")]
 use web_sys::MouseEvent;
 use log::info;
 
@@ -12,6 +18,7 @@ pub struct Rect {
     height: f32,
 }
 
+#[derive(Clone)]
 pub enum Component {
     Button { rect: Rect, text: String, on_click: Option<fn() -> ()> },
     Slider { rect: Rect, value: f32, min: f32, max: f32, on_change: Option<fn(f32) -> ()> },
@@ -19,24 +26,291 @@ pub enum Component {
     Image { rect: Rect, texture: wgpu::Texture },
 }
 
+impl Component {
+    fn rect(&self) -> Rect {
+        match self {
+            Component::Button { rect, .. } => *rect,
+            Component::Slider { rect, .. } => *rect,
+            Component::Text { rect, .. } => *rect,
+            Component::Image { rect, .. } => *rect,
+        }
+    }
+}
+
+// An asset to decode/rasterize for `Pixll::prepare_assets`: the CPU-side
+// work (image decoding, glyph rasterization) that can run off the main
+// thread, ahead of the GPU upload that has to happen on it.
+pub enum AssetSpec {
+    Image(Vec<u8>),
+    Glyph { font_bytes: Vec<u8>, ch: char, px: f32 },
+}
+
+// The result of preparing an `AssetSpec`: a `wgpu::Texture` ready to hand to
+// `Component::Image`, or a rasterized glyph bitmap plus the metrics needed to
+// lay it out, mirroring `GlyphInfo`'s fields.
+pub enum GpuAsset {
+    Image(wgpu::Texture),
+    Glyph { bitmap: Vec<u8>, width: u32, height: u32, bearing: (f32, f32), advance: f32 },
+}
+
+// CPU-side result of decoding/rasterizing one `AssetSpec`, produced in
+// parallel by `prepare_assets` before the GPU upload step that turns it into
+// a `GpuAsset`. `prepare_assets` wraps each one in a `Result` whose `Err`
+// carries the decode/parse failure message it reports back for that asset,
+// mirroring `set_font`.
+enum PreparedAsset {
+    Image { width: u32, height: u32, pixels: Vec<u8> },
+    Glyph { bitmap: Vec<u8>, width: u32, height: u32, bearing: (f32, f32), advance: f32 },
+}
+
+// The unit quad shared by every instance: local corners in [-1, 1], scaled
+// and offset per-instance in the vertex shader. Triangle-strip order is
+// top-left, bottom-left, top-right, bottom-right.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct QuadVertex {
+    position: [f32; 2],
+}
+
+impl QuadVertex {
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<QuadVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[wgpu::VertexAttribute {
+                offset: 0,
+                shader_location: 0,
+                format: wgpu::VertexFormat::Float32x2,
+            }],
+        }
+    }
+}
+
+const UNIT_QUAD: [QuadVertex; 4] = [
+    QuadVertex { position: [-1.0, 1.0] },
+    QuadVertex { position: [-1.0, -1.0] },
+    QuadVertex { position: [1.0, 1.0] },
+    QuadVertex { position: [1.0, -1.0] },
+];
+
+// Per-component instance data for the instanced flat-quad pipeline: clip-space
+// center/offset, clip-space half-extents, an RGBA fill color, and a depth
+// value so overlapping components composite via the depth test instead of
+// draw order. `pixel_half_extent`, `corner_radius`, `border_width`, and
+// `border_color` feed the fragment shader's rounded-rect signed-distance
+// field, which needs the quad's half-extent in the same (pixel) units as the
+// radius/border width -- the clip-space `half_extent` above is anisotropic
+// with the surface aspect ratio and can't be reused for that.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Instance {
+    offset: [f32; 2],
+    half_extent: [f32; 2],
+    color: [f32; 4],
+    z: f32,
+    pixel_half_extent: [f32; 2],
+    corner_radius: f32,
+    border_width: f32,
+    border_color: [f32; 4],
+}
+
+impl Instance {
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 9]>() as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 11]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 13]>() as wgpu::BufferAddress,
+                    shader_location: 8,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
+
+const QUAD_COLOR: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
+const QUAD_CORNER_RADIUS: f32 = 8.0;
+const QUAD_BORDER_WIDTH: f32 = 2.0;
+const QUAD_BORDER_COLOR: [f32; 4] = [0.2, 0.2, 0.2, 1.0];
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+// Vertex layout for the texture pipeline: clip-space position, UVs in
+// [0, 1], and a depth value, used for both `Component::Image` quads and
+// glyph-atlas quads.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct TexVertex {
+    position: [f32; 2],
+    tex_coords: [f32; 2],
+    z: f32,
+}
+
+impl TexVertex {
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TexVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32,
+                },
+            ],
+        }
+    }
+}
+
+// One indexed draw call against a single texture (an `Image`'s own texture,
+// or the shared glyph atlas for all `Text` quads in a frame).
+struct TexturedBatch {
+    bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
+}
+
+// Where a rasterized glyph lives in the atlas, plus the metrics needed to
+// advance the pen and position it relative to the text baseline.
+struct GlyphInfo {
+    uv_min: [f32; 2],
+    uv_max: [f32; 2],
+    size: (f32, f32),
+    bearing: (f32, f32),
+    advance: f32,
+}
+
+struct GlyphAtlas {
+    bind_group: wgpu::BindGroup,
+    glyphs: HashMap<char, GlyphInfo>,
+}
+
+const ATLAS_SIZE: u32 = 512;
+const ATLAS_FONT_PX: f32 = 32.0;
+
+// The scene texture and its two ping-pong buffers are created with the
+// surface's own format (not a fixed one) so the UI and effect pipelines,
+// which are built once against `surface_config.format`, stay valid as the
+// render target whether or not a post-processing chain is active. Since the
+// scene format always matches the surface, the blit pass still needs no
+// format conversion.
+
+// Uniform block handed to every post-effect shader alongside the scene
+// texture/sampler: the surface size in pixels and an elapsed-time clock, so
+// effects like vignettes or color grading can react to aspect ratio or
+// animate. `time` is derived from a render-call counter rather than a wall
+// clock, since there's no per-platform clock wired up yet.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct EffectUniforms {
+    resolution: [f32; 2],
+    time: f32,
+    _padding: f32,
+}
+
+const BLIT_FRAGMENT_SHADER: &str = "
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(t_scene, s_scene, in.uv);
+}
+";
+
 pub struct Pixll {
     device: wgpu::Device,
     queue: wgpu::Queue,
     surface: wgpu::Surface<'static>,
     surface_config: wgpu::SurfaceConfiguration,
-    render_pipeline: wgpu::RenderPipeline,
-    vertex_buffer: wgpu::Buffer,
+    depth_view: wgpu::TextureView,
+    instance_pipeline: wgpu::RenderPipeline,
+    quad_vertex_buffer: wgpu::Buffer,
+    instance_buffer: wgpu::Buffer,
+    num_instances: u32,
+    texture_pipeline: wgpu::RenderPipeline,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    texture_sampler: wgpu::Sampler,
+    textured_batches: Vec<TexturedBatch>,
+    font: Option<fontdue::Font>,
+    glyph_atlas: Option<GlyphAtlas>,
+    geometry_dirty: bool,
     components: Vec<Component>,
     mouse_pos: (f32, f32),
     layout: Vec<(Component, Rect)>,
+    // Scissor rect (in pixel space) clipping every draw this frame, for
+    // scrollable/overflow-hidden regions. Applies to the whole canvas rather
+    // than a single component, since there's no container/parenting concept
+    // yet -- a real nested-scroll-region API would hang this off individual
+    // components instead.
+    scroll_clip: Option<Rect>,
+    // Post-processing chain: empty by default, which keeps `render` on the
+    // original direct-to-surface path. Once non-empty, the UI draws into
+    // `scene_view` instead, each effect bounces between `post_a_view`/
+    // `post_b_view`, and `blit_pipeline` copies the last effect's output to
+    // the surface.
+    scene_view: wgpu::TextureView,
+    post_a_view: wgpu::TextureView,
+    post_b_view: wgpu::TextureView,
+    effect_sampler: wgpu::Sampler,
+    effect_bind_group_layout: wgpu::BindGroupLayout,
+    effect_pipeline_layout: wgpu::PipelineLayout,
+    effect_uniform_buffer: wgpu::Buffer,
+    blit_pipeline: wgpu::RenderPipeline,
+    effects: Vec<wgpu::RenderPipeline>,
+    frame_count: u32,
 }
 
 impl Pixll {
+    #[cfg(target_arch = "wasm32")]
     pub async fn new(canvas: HtmlCanvasElement) -> Result<Self, JsValue> {
         console_log::init_with_level(log::Level::Info).unwrap();
         console_error_panic_hook::set_once();
 
-        // Set up WebGPU
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
             backends: wgpu::Backends::all(),
             ..Default::default()
@@ -59,10 +333,9 @@ impl Pixll {
         ).await.map_err(|e| JsValue::from_str(&format!("{:?}", e)))?;
 
         let surface_caps = surface.get_capabilities(&adapter);
-        let surface_format = surface_caps.formats[0];
         let surface_config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
+            format: surface_caps.formats[0],
             width: canvas.width(),
             height: canvas.height(),
             present_mode: wgpu::PresentMode::Fifo,
@@ -72,46 +345,99 @@ impl Pixll {
         };
         surface.configure(&device, &surface_config);
 
-        // Create a simple render pipeline
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+        Self::from_parts(device, queue, surface, surface_config)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    // Desktop/Android entry point: builds the surface from a winit window
+    // (via `instance.create_surface`) instead of an `HtmlCanvasElement`, so
+    // the same component and layout code renders outside the browser. On
+    // Android, don't call this until the native window exists -- construct
+    // it from `Event::Resumed`, not eagerly at app startup, since winit only
+    // hands out a window at that point.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn new_windowed(window: std::sync::Arc<winit::window::Window>) -> Result<Self, String> {
+        let size = window.inner_size();
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
         });
 
-        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Render Pipeline Layout"),
+        let surface = instance.create_surface(window).map_err(|e| format!("{:?}", e))?;
+        let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            compatible_surface: Some(&surface),
+            force_fallback_adapter: false,
+        }).await.ok_or("Failed to find an appropriate adapter")?;
+
+        let (device, queue) = adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::default(),
+                label: None,
+            },
+            None,
+        ).await.map_err(|e| format!("{:?}", e))?;
+
+        let surface_caps = surface.get_capabilities(&adapter);
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_caps.formats[0],
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &surface_config);
+
+        Self::from_parts(device, queue, surface, surface_config)
+    }
+
+    // Shared pipeline/buffer setup once a device, queue, and already-configured
+    // surface exist, regardless of which platform produced them.
+    fn from_parts(
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        surface: wgpu::Surface<'static>,
+        surface_config: wgpu::SurfaceConfiguration,
+    ) -> Result<Self, String> {
+        let depth_view = Self::create_depth_view(&device, &surface_config);
+
+        let depth_stencil_state = wgpu::DepthStencilState {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        };
+
+        // Instanced pipeline: a single unit quad transformed per-instance, so
+        // all flat-colored Button/Slider rects draw in one `draw` call
+        // regardless of how many components there are.
+        let instance_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Instance Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("instance_shader.wgsl").into()),
+        });
+
+        let instance_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Instance Pipeline Layout"),
             bind_group_layouts: &[],
             push_constant_ranges: &[],
         });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
+        let instance_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Instance Pipeline"),
+            layout: Some(&instance_pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &shader,
+                module: &instance_shader,
                 entry_point: "vs_main",
-                buffers: &[
-                    wgpu::VertexBufferLayout {
-                        array_stride: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
-                        step_mode: wgpu::VertexStepMode::Vertex,
-                        attributes: &[
-                            wgpu::VertexAttribute {
-                                offset: 0,
-                                shader_location: 0,
-                                format: wgpu::VertexFormat::Float32x2,
-                            },
-                            wgpu::VertexAttribute {
-                                offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
-                                shader_location: 1,
-                                format: wgpu::VertexFormat::Float32x2,
-                            },
-                        ],
-                    },
-                ],
+                buffers: &[QuadVertex::desc(), Instance::desc()],
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
-                module: &shader,
+                module: &instance_shader,
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
                     format: surface_config.format,
@@ -120,6 +446,95 @@ impl Pixll {
                 })],
                 compilation_options: Default::default(),
             }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(depth_stencil_state.clone()),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        let quad_vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Quad Vertex Buffer"),
+            contents: bytemuck::cast_slice(&UNIT_QUAD),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        // Second pipeline: textured quads, shared by `Image` components and
+        // the glyph atlas. Alpha blending so glyph coverage composites
+        // correctly over whatever was already drawn.
+        let texture_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Texture Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("texture_shader.wgsl").into()),
+        });
+
+        let texture_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Texture Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let texture_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Texture Pipeline Layout"),
+            bind_group_layouts: &[&texture_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let texture_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Texture Pipeline"),
+            layout: Some(&texture_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &texture_shader,
+                entry_point: "vs_main",
+                buffers: &[TexVertex::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &texture_shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
             primitive: wgpu::PrimitiveState {
                 topology: wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
@@ -129,7 +544,7 @@ impl Pixll {
                 unclipped_depth: false,
                 conservative: false,
             },
-            depth_stencil: None,
+            depth_stencil: Some(depth_stencil_state),
             multisample: wgpu::MultisampleState {
                 count: 1,
                 mask: !0,
@@ -138,46 +553,606 @@ impl Pixll {
             multiview: None,
         });
 
-        // Create a vertex buffer for a simple rectangle (test rendering)
-        let vertices: &[f32] = &[
-            // Position (x, y), Color (r, g)
-            -0.5, -0.5, 1.0, 0.0,  // Bottom-left
-             0.5, -0.5, 0.0, 1.0,  // Bottom-right
-             0.0,  0.5, 0.0, 0.0,  // Top
-        ];
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Vertex Buffer"),
-            contents: bytemuck::cast_slice(vertices),
+        // The instance buffer is rebuilt lazily on the first render (see
+        // `geometry_dirty`), so it starts out empty.
+        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instance Buffer"),
+            contents: &[],
             usage: wgpu::BufferUsages::VERTEX,
         });
 
+        let (scene_view, post_a_view, post_b_view) = Self::create_offscreen_targets(&device, &surface_config);
+
+        let effect_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Effect Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let effect_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Effect Pipeline Layout"),
+            bind_group_layouts: &[&effect_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let effect_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let effect_uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Effect Uniform Buffer"),
+            size: std::mem::size_of::<EffectUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Fixed final pass of the post-processing chain: samples whatever the
+        // last effect wrote and copies it into the surface format. The scene
+        // and effect pipelines already target `surface_config.format`, so
+        // this is a same-format copy rather than a conversion.
+        let blit_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Blit Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                format!("{}\n{}", include_str!("post_common.wgsl"), BLIT_FRAGMENT_SHADER).into(),
+            ),
+        });
+
+        let blit_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Blit Pipeline"),
+            layout: Some(&effect_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &blit_shader,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &blit_shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
         Ok(Self {
             device,
             queue,
             surface,
             surface_config,
-            render_pipeline,
-            vertex_buffer,
+            depth_view,
+            instance_pipeline,
+            quad_vertex_buffer,
+            instance_buffer,
+            num_instances: 0,
+            texture_pipeline,
+            texture_bind_group_layout,
+            texture_sampler,
+            textured_batches: Vec::new(),
+            font: None,
+            glyph_atlas: None,
+            geometry_dirty: true,
             components: Vec::new(),
+            mouse_pos: (0.0, 0.0),
+            layout: Vec::new(),
+            scroll_clip: None,
+            scene_view,
+            post_a_view,
+            post_b_view,
+            effect_sampler,
+            effect_bind_group_layout,
+            effect_pipeline_layout,
+            effect_uniform_buffer,
+            blit_pipeline,
+            effects: Vec::new(),
+            frame_count: 0,
         })
     }
 
+    // Sized to the surface so overlapping components (e.g. a modal over a
+    // button) composite via the depth test instead of draw order.
+    fn create_depth_view(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::TextureView {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Depth Texture"),
+            size: wgpu::Extent3d { width: config.width, height: config.height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    // Builds the scene texture the UI renders into plus the two ping-pong
+    // buffers the post-effect chain bounces between, all in the surface's own
+    // format (matching the instance/texture/blit pipelines) and sized to the
+    // surface so effects sample at native resolution.
+    fn create_offscreen_targets(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+    ) -> (wgpu::TextureView, wgpu::TextureView, wgpu::TextureView) {
+        let make_target = |label: &str| {
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d { width: config.width.max(1), height: config.height.max(1), depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: config.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            });
+            texture.create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        (make_target("Scene Texture"), make_target("Post Effect Texture A"), make_target("Post Effect Texture B"))
+    }
+
     pub fn add_component(&mut self, component: Component) {
         self.components.push(component);
+        self.geometry_dirty = true;
+    }
+
+    // Sets (or clears) the pixel-space rect that every draw is scissored to
+    // this frame, for overflow-hidden/scrollable regions. `None` restores the
+    // full surface as the clip.
+    pub fn set_scroll_clip(&mut self, clip: Option<Rect>) {
+        self.scroll_clip = clip;
+    }
+
+    // Appends a post-processing pass to the effect chain. `wgsl` is just the
+    // fragment shader: it's compiled together with the shared full-screen
+    // vertex shader and the `t_scene`/`s_scene`/`uniforms` bindings declared
+    // in `post_common.wgsl`, so effects only need to write `fs_main`. The
+    // chain is empty by default, so `render` stays on the direct-to-surface
+    // path until the first effect is pushed.
+    pub fn push_effect(&mut self, wgsl: &str) {
+        let source = format!("{}\n{}", include_str!("post_common.wgsl"), wgsl);
+        let shader = self.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Post Effect Shader"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+
+        let pipeline = self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Post Effect Pipeline"),
+            layout: Some(&self.effect_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: self.surface_config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        self.effects.push(pipeline);
+    }
+
+    // Loads a font, rasterizes the printable ASCII range into a single atlas
+    // texture, and triggers a geometry rebuild so any `Text` components pick
+    // up real glyph quads instead of rendering nothing.
+    pub fn set_font(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let font = fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default())?;
+        self.font = Some(font);
+        self.rebuild_glyph_atlas()?;
+        self.geometry_dirty = true;
+        Ok(())
+    }
+
+    // `ATLAS_SIZE` is a fixed budget sized for typical fonts at
+    // `ATLAS_FONT_PX`, but a caller-supplied font's glyph metrics for the
+    // printable ASCII range aren't guaranteed to fit it -- report that
+    // instead of indexing past the end of `pixels`.
+    fn rebuild_glyph_atlas(&mut self) -> Result<(), String> {
+        let Some(font) = &self.font else { return Ok(()) };
+
+        let mut pixels = vec![0u8; (ATLAS_SIZE * ATLAS_SIZE * 4) as usize];
+        let mut glyphs = HashMap::new();
+        let (mut pen_x, mut pen_y, mut row_height) = (0u32, 0u32, 0u32);
+
+        for code in 0x20u32..0x7f {
+            let ch = char::from_u32(code).unwrap();
+            let (metrics, bitmap) = font.rasterize(ch, ATLAS_FONT_PX);
+            let (glyph_w, glyph_h) = (metrics.width as u32, metrics.height as u32);
+
+            if pen_x + glyph_w > ATLAS_SIZE {
+                pen_x = 0;
+                pen_y += row_height + 1;
+                row_height = 0;
+            }
+
+            if pen_x + glyph_w > ATLAS_SIZE || pen_y + glyph_h > ATLAS_SIZE {
+                return Err(format!(
+                    "font glyphs don't fit the {ATLAS_SIZE}x{ATLAS_SIZE} atlas at {ATLAS_FONT_PX}px"
+                ));
+            }
+
+            for y in 0..glyph_h {
+                for x in 0..glyph_w {
+                    let coverage = bitmap[(y * glyph_w + x) as usize];
+                    let idx = (((pen_y + y) * ATLAS_SIZE + (pen_x + x)) * 4) as usize;
+                    // White RGB with coverage carried only in alpha, so
+                    // `texture_pipeline`'s straight alpha blend (`SrcAlpha`/
+                    // `OneMinusSrcAlpha`) composites edge coverage once
+                    // instead of the `coverage * coverage` darkening that
+                    // baking coverage into RGB as well would cause.
+                    pixels[idx..idx + 4].copy_from_slice(&[255, 255, 255, coverage]);
+                }
+            }
+
+            glyphs.insert(ch, GlyphInfo {
+                uv_min: [pen_x as f32 / ATLAS_SIZE as f32, pen_y as f32 / ATLAS_SIZE as f32],
+                uv_max: [(pen_x + glyph_w) as f32 / ATLAS_SIZE as f32, (pen_y + glyph_h) as f32 / ATLAS_SIZE as f32],
+                size: (glyph_w as f32, glyph_h as f32),
+                bearing: (metrics.xmin as f32, metrics.ymin as f32),
+                advance: metrics.advance_width,
+            });
+
+            pen_x += glyph_w + 1;
+            row_height = row_height.max(glyph_h);
+        }
+
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Glyph Atlas"),
+            size: wgpu::Extent3d { width: ATLAS_SIZE, height: ATLAS_SIZE, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        self.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * ATLAS_SIZE),
+                rows_per_image: Some(ATLAS_SIZE),
+            },
+            wgpu::Extent3d { width: ATLAS_SIZE, height: ATLAS_SIZE, depth_or_array_layers: 1 },
+        );
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Glyph Atlas Bind Group"),
+            layout: &self.texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.texture_sampler) },
+            ],
+        });
+
+        self.glyph_atlas = Some(GlyphAtlas { bind_group, glyphs });
+        Ok(())
+    }
+
+    // Decodes image bytes and rasterizes glyphs across threads via rayon,
+    // then uploads each decoded image to the GPU back on the main thread
+    // (glyphs come back as CPU-side bitmaps, not uploaded) -- only
+    // the CPU-side work is safe to parallelize, since `write_texture` needs
+    // `&self.queue`/`&self.device`. Native-only: the basic wasm32 target has
+    // no threads for rayon to use. A malformed asset reports its own `Err`
+    // (mirroring `set_font`) rather than panicking the whole batch -- the
+    // caller supplies this data, and one bad image or font shouldn't take
+    // down every other asset already decoded alongside it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn prepare_assets(&self, assets: Vec<AssetSpec>) -> Vec<Result<GpuAsset, String>> {
+        use rayon::prelude::*;
+
+        let prepared: Vec<Result<PreparedAsset, String>> = assets
+            .into_par_iter()
+            .map(|spec| match spec {
+                AssetSpec::Image(bytes) => {
+                    let decoded = image::load_from_memory(&bytes)
+                        .map_err(|e| format!("failed to decode image asset: {e}"))?
+                        .to_rgba8();
+                    let (width, height) = decoded.dimensions();
+                    Ok(PreparedAsset::Image { width, height, pixels: decoded.into_raw() })
+                }
+                AssetSpec::Glyph { font_bytes, ch, px } => {
+                    let font = fontdue::Font::from_bytes(font_bytes.as_slice(), fontdue::FontSettings::default())?;
+                    let (metrics, bitmap) = font.rasterize(ch, px);
+                    Ok(PreparedAsset::Glyph {
+                        bitmap,
+                        width: metrics.width as u32,
+                        height: metrics.height as u32,
+                        bearing: (metrics.xmin as f32, metrics.ymin as f32),
+                        advance: metrics.advance_width,
+                    })
+                }
+            })
+            .collect();
+
+        prepared
+            .into_iter()
+            .map(|asset| match asset? {
+                PreparedAsset::Image { width, height, pixels } => {
+                    let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+                        label: Some("Prepared Image Texture"),
+                        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: wgpu::TextureDimension::D2,
+                        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                        view_formats: &[],
+                    });
+                    self.queue.write_texture(
+                        wgpu::ImageCopyTexture {
+                            texture: &texture,
+                            mip_level: 0,
+                            origin: wgpu::Origin3d::ZERO,
+                            aspect: wgpu::TextureAspect::All,
+                        },
+                        &pixels,
+                        wgpu::ImageDataLayout {
+                            offset: 0,
+                            bytes_per_row: Some(4 * width),
+                            rows_per_image: Some(height),
+                        },
+                        wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+                    );
+                    Ok(GpuAsset::Image(texture))
+                }
+                PreparedAsset::Glyph { bitmap, width, height, bearing, advance } => {
+                    Ok(GpuAsset::Glyph { bitmap, width, height, bearing, advance })
+                }
+            })
+            .collect()
+    }
+
+    // The (component, rect) pairs to draw this frame: the laid-out positions
+    // if `layout_vertical` has run, otherwise each component's own rect.
+    fn items(&self) -> Vec<(&Component, Rect)> {
+        if !self.layout.is_empty() {
+            self.layout.iter().map(|(comp, rect)| (comp, *rect)).collect()
+        } else {
+            self.components.iter().map(|comp| (comp, comp.rect())).collect()
+        }
     }
 
-    pub fn render(&self) -> Result<(), wgpu::SurfaceError> {
+    // Pixel space (origin top-left) to clip space (-1..1, origin center, y up),
+    // mirroring the `pixel_to_clip` helper from the pixll_ui WASM module.
+    fn pixel_to_clip(&self, x: f32, y: f32) -> (f32, f32) {
+        let width = self.surface_config.width as f32;
+        let height = self.surface_config.height as f32;
+        (x / width * 2.0 - 1.0, 1.0 - y / height * 2.0)
+    }
+
+    fn rect_to_clip(&self, rect: &Rect) -> (f32, f32, f32, f32) {
+        let (left, top) = self.pixel_to_clip(rect.x, rect.y);
+        let (right, bottom) = self.pixel_to_clip(rect.x + rect.width, rect.y + rect.height);
+        (left, top, right, bottom)
+    }
+
+    // Walks the current components/layout and builds one instance per
+    // Button/Slider for the instanced pipeline, plus one textured batch per
+    // `Image` and one shared batch for all `Text` glyphs. Only called when
+    // `geometry_dirty` is set, so static UIs don't re-upload every frame.
+    //
+    // Each item's depth is derived from its position in the draw order: later
+    // components get a smaller NDC z, so they occlude earlier ones regardless
+    // of which pipeline draws them, without callers having to set a z by hand.
+    fn build_geometry(&mut self) {
+        let items = self.items();
+        let total = items.len();
+        let mut instances = Vec::new();
+        let mut textured_batches = Vec::new();
+        let mut text_vertices: Vec<TexVertex> = Vec::new();
+        let mut text_indices: Vec<u16> = Vec::new();
+
+        for (i, (component, rect)) in items.iter().enumerate() {
+            let z = 1.0 - (i + 1) as f32 / (total + 1) as f32;
+
+            match component {
+                Component::Button { .. } | Component::Slider { .. } => {
+                    let (left, top, right, bottom) = self.rect_to_clip(rect);
+                    instances.push(Instance {
+                        offset: [(left + right) / 2.0, (top + bottom) / 2.0],
+                        half_extent: [(right - left) / 2.0, (top - bottom) / 2.0],
+                        color: QUAD_COLOR,
+                        z,
+                        pixel_half_extent: [rect.width / 2.0, rect.height / 2.0],
+                        corner_radius: QUAD_CORNER_RADIUS,
+                        border_width: QUAD_BORDER_WIDTH,
+                        border_color: QUAD_BORDER_COLOR,
+                    });
+                }
+                Component::Image { texture, .. } => {
+                    let (left, top, right, bottom) = self.rect_to_clip(rect);
+                    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                    let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                        label: Some("Image Bind Group"),
+                        layout: &self.texture_bind_group_layout,
+                        entries: &[
+                            wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
+                            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.texture_sampler) },
+                        ],
+                    });
+                    let image_vertices = [
+                        TexVertex { position: [left, top], tex_coords: [0.0, 0.0], z },
+                        TexVertex { position: [left, bottom], tex_coords: [0.0, 1.0], z },
+                        TexVertex { position: [right, top], tex_coords: [1.0, 0.0], z },
+                        TexVertex { position: [right, bottom], tex_coords: [1.0, 1.0], z },
+                    ];
+                    let image_indices: [u16; 6] = [0, 1, 2, 2, 1, 3];
+                    textured_batches.push(TexturedBatch {
+                        bind_group,
+                        vertex_buffer: self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                            label: Some("Image Vertex Buffer"),
+                            contents: bytemuck::cast_slice(&image_vertices),
+                            usage: wgpu::BufferUsages::VERTEX,
+                        }),
+                        index_buffer: self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                            label: Some("Image Index Buffer"),
+                            contents: bytemuck::cast_slice(&image_indices),
+                            usage: wgpu::BufferUsages::INDEX,
+                        }),
+                        num_indices: image_indices.len() as u32,
+                    });
+                }
+                Component::Text { content, .. } => {
+                    let Some(atlas) = &self.glyph_atlas else { continue };
+                    let scale = rect.height / ATLAS_FONT_PX;
+                    let mut pen_x = rect.x;
+                    let baseline_y = rect.y + rect.height;
+
+                    for ch in content.chars() {
+                        let Some(glyph) = atlas.glyphs.get(&ch) else { continue };
+                        let x0 = pen_x + glyph.bearing.0 * scale;
+                        let x1 = x0 + glyph.size.0 * scale;
+                        let y1 = baseline_y - glyph.bearing.1 * scale;
+                        let y0 = y1 - glyph.size.1 * scale;
+
+                        let (left, top) = self.pixel_to_clip(x0, y0);
+                        let (right, bottom) = self.pixel_to_clip(x1, y1);
+                        let base = text_vertices.len() as u16;
+                        text_vertices.push(TexVertex { position: [left, top], tex_coords: [glyph.uv_min[0], glyph.uv_min[1]], z });
+                        text_vertices.push(TexVertex { position: [left, bottom], tex_coords: [glyph.uv_min[0], glyph.uv_max[1]], z });
+                        text_vertices.push(TexVertex { position: [right, top], tex_coords: [glyph.uv_max[0], glyph.uv_min[1]], z });
+                        text_vertices.push(TexVertex { position: [right, bottom], tex_coords: [glyph.uv_max[0], glyph.uv_max[1]], z });
+                        text_indices.extend_from_slice(&[base, base + 1, base + 2, base + 2, base + 1, base + 3]);
+
+                        pen_x += glyph.advance * scale;
+                    }
+                }
+            }
+        }
+
+        if let (Some(atlas), false) = (&self.glyph_atlas, text_vertices.is_empty()) {
+            textured_batches.push(TexturedBatch {
+                bind_group: atlas.bind_group.clone(),
+                vertex_buffer: self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Text Vertex Buffer"),
+                    contents: bytemuck::cast_slice(&text_vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                }),
+                index_buffer: self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Text Index Buffer"),
+                    contents: bytemuck::cast_slice(&text_indices),
+                    usage: wgpu::BufferUsages::INDEX,
+                }),
+                num_indices: text_indices.len() as u32,
+            });
+        }
+
+        self.instance_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instance Buffer"),
+            contents: bytemuck::cast_slice(&instances),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        self.num_instances = instances.len() as u32;
+        self.textured_batches = textured_batches;
+        self.geometry_dirty = false;
+    }
+
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        if self.geometry_dirty {
+            self.build_geometry();
+        }
+
         let output = self.surface.get_current_texture()?;
         let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
         let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         });
 
+        // With no effects pushed, the UI renders straight to the swapchain,
+        // exactly like before the post-processing chain existed. Once an
+        // effect is pushed, it renders into the offscreen scene texture
+        // instead, and the chain (built below) is responsible for getting
+        // pixels onto the surface.
+        let ui_target = if self.effects.is_empty() { &view } else { &self.scene_view };
+
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view: ui_target,
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(wgpu::Color {
@@ -189,14 +1164,127 @@ impl Pixll {
                         store: wgpu::StoreOp::Store,
                     },
                 })],
-                depth_stencil_attachment: None,
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
                 occlusion_query_set: None,
                 timestamp_writes: None,
             });
 
-            render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.draw(0..3, 0..1);
+            // A clip scrolled fully past the surface edge (x/y >= width/height,
+            // an ordinary state for overflow-hidden content scrolled past its
+            // bounds) clamps to a zero-size rect -- skip the scissor call and
+            // the draws under it rather than flooring to a stray 1px rect
+            // that `set_scissor_rect` would reject as out of bounds.
+            let clip_is_empty = if let Some(clip) = &self.scroll_clip {
+                let width = self.surface_config.width;
+                let height = self.surface_config.height;
+                let x = (clip.x.max(0.0) as u32).min(width);
+                let y = (clip.y.max(0.0) as u32).min(height);
+                let w = (clip.width.max(0.0) as u32).min(width.saturating_sub(x));
+                let h = (clip.height.max(0.0) as u32).min(height.saturating_sub(y));
+                if w == 0 || h == 0 {
+                    true
+                } else {
+                    render_pass.set_scissor_rect(x, y, w, h);
+                    false
+                }
+            } else {
+                false
+            };
+
+            if !clip_is_empty {
+                render_pass.set_pipeline(&self.instance_pipeline);
+                render_pass.set_vertex_buffer(0, self.quad_vertex_buffer.slice(..));
+                render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+                render_pass.draw(0..4, 0..self.num_instances);
+
+                render_pass.set_pipeline(&self.texture_pipeline);
+                for batch in &self.textured_batches {
+                    render_pass.set_bind_group(0, &batch.bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, batch.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(batch.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                    render_pass.draw_indexed(0..batch.num_indices, 0, 0..1);
+                }
+            }
+        }
+
+        if !self.effects.is_empty() {
+            self.frame_count = self.frame_count.wrapping_add(1);
+            let uniforms = EffectUniforms {
+                resolution: [self.surface_config.width as f32, self.surface_config.height as f32],
+                time: self.frame_count as f32 / 60.0,
+                _padding: 0.0,
+            };
+            self.queue.write_buffer(&self.effect_uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+
+            // Ping-pong the chain between `post_a_view`/`post_b_view`,
+            // starting from the scene texture the UI just drew into.
+            let mut src_view = &self.scene_view;
+            let mut ping = true;
+            for pipeline in &self.effects {
+                let dst_view = if ping { &self.post_a_view } else { &self.post_b_view };
+                let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Effect Bind Group"),
+                    layout: &self.effect_bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(src_view) },
+                        wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.effect_sampler) },
+                        wgpu::BindGroupEntry { binding: 2, resource: self.effect_uniform_buffer.as_entire_binding() },
+                    ],
+                });
+
+                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Post Effect Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: dst_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::BLACK), store: wgpu::StoreOp::Store },
+                    })],
+                    depth_stencil_attachment: None,
+                    occlusion_query_set: None,
+                    timestamp_writes: None,
+                });
+                pass.set_pipeline(pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                pass.draw(0..3, 0..1);
+                drop(pass);
+
+                src_view = dst_view;
+                ping = !ping;
+            }
+
+            // Final blit from the last effect's output to the swapchain.
+            let blit_bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Blit Bind Group"),
+                layout: &self.effect_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(src_view) },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.effect_sampler) },
+                    wgpu::BindGroupEntry { binding: 2, resource: self.effect_uniform_buffer.as_entire_binding() },
+                ],
+            });
+
+            let mut blit_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Blit Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::BLACK), store: wgpu::StoreOp::Store },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            blit_pass.set_pipeline(&self.blit_pipeline);
+            blit_pass.set_bind_group(0, &blit_bind_group, &[]);
+            blit_pass.draw(0..3, 0..1);
+            drop(blit_pass);
         }
 
         self.queue.submit(std::iter::once(encoder.finish()));
@@ -209,8 +1297,17 @@ impl Pixll {
         self.surface_config.width = width;
         self.surface_config.height = height;
         self.surface.configure(&self.device, &self.surface_config);
+        self.depth_view = Self::create_depth_view(&self.device, &self.surface_config);
+        let (scene_view, post_a_view, post_b_view) = Self::create_offscreen_targets(&self.device, &self.surface_config);
+        self.scene_view = scene_view;
+        self.post_a_view = post_a_view;
+        self.post_b_view = post_b_view;
+        self.geometry_dirty = true;
     }
 
+    // Web-only for now: native input (winit `WindowEvent`s) isn't wired up
+    // yet, since this pass only made the GPU backend cross-platform.
+    #[cfg(target_arch = "wasm32")]
     pub fn handle_mouse_click(&mut self, event: MouseEvent) {
         let x = event.offset_x() as f32;
         let y = event.offset_y() as f32;
@@ -255,5 +1352,6 @@ impl Pixll {
             y += rect.height + spacing;
         }
         self.components = self.layout.iter().map(|(comp, _)| comp.clone()).collect();
+        self.geometry_dirty = true;
     }
-}
\ No newline at end of file
+}